@@ -60,9 +60,10 @@ pub struct Package {
 }
 
 impl Package {
-	///	The name of the binary tarball this Package will produce.
-	pub fn tarball(&self, output: &Path) -> PathBuf {
-		output.join(format!("{}-{}-x86_64.tar.gz", self.name, self.version))
+	///	The name of the binary tarball this Package will produce for the
+	///	given Arch architecture name and compression format.
+	pub fn tarball(&self, output: &Path, arch: &str, compression: Compression) -> PathBuf {
+		output.join(format!("{}-{}-{}.{}", self.name, self.version, arch, compression.extension()))
 	}
 
 	///	The git host of this package's repository.
@@ -75,6 +76,57 @@ impl Package {
 	}
 }
 
+///	Maps a Rust target triple to the Arch Linux architecture name used in a
+///	PKGBUILD's `arch` array. Unrecognized triples are passed through
+///	unchanged, so an unlisted target can still be attempted.
+pub fn arch_name(triple: &str) -> &str {
+	if triple.starts_with("x86_64") {
+		"x86_64"
+	} else if triple.starts_with("aarch64") {
+		"aarch64"
+	} else if triple.starts_with("armv7") {
+		"armv7h"
+	} else {
+		triple
+	}
+}
+
+///	The tarball compression format to use, configurable via
+///	`[package.metadata.aur] compression`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+	Gzip,
+	Xz,
+	Zstd,
+}
+
+impl Default for Compression {
+	fn default() -> Compression {
+		Compression::Gzip
+	}
+}
+
+impl Compression {
+	///	The `tar` flag(s) that select this compression format.
+	pub fn tar_args(&self) -> &'static [&'static str] {
+		match self {
+			Compression::Gzip => &["czf"],
+			Compression::Xz => &["cJf"],
+			Compression::Zstd => &["--zstd", "-cf"],
+		}
+	}
+
+	///	The file extension produced by this compression format.
+	pub fn extension(&self) -> &'static str {
+		match self {
+			Compression::Gzip => "tar.gz",
+			Compression::Xz => "tar.xz",
+			Compression::Zstd => "tar.zst",
+		}
+	}
+}
+
 ///	`[package.metadata]` TOML block, used to access `[package.metadata.aur]`.
 #[derive(Debug, Default, Deserialize)]
 pub struct Metadata {
@@ -85,6 +137,11 @@ pub struct Metadata {
 ///	The values of a `[package.metadata.aur]` TOML block.
 #[derive(Debug, Deserialize)]
 pub struct Aur {
+	///	Override the release tarball's location instead of deriving it from
+	///	the repository's git host. Supports a `{arch}` placeholder, which is
+	///	replaced with the current architecture's name — required if
+	///	`targets` declares more than one architecture, or every one of them
+	///	will resolve to the same archive/checksum.
 	#[serde(default)]
 	pub archive: Option<String>,
 	#[serde(default)]
@@ -93,6 +150,34 @@ pub struct Aur {
 	pub depends: Vec<String>,
 	#[serde(default)]
 	pub optdepends: Vec<String>,
+	///	Rust target triples to cross-build and package, e.g.
+	///	`aarch64-unknown-linux-gnu`. When empty, the host's default target
+	///	(or the musl target with `--musl`) is used and the PKGBUILD stays
+	///	single-architecture.
+	#[serde(default)]
+	pub targets: Vec<String>,
+	///	The container base image used by `--verify`. Defaults to
+	///	`archlinux` when unset.
+	#[serde(default)]
+	pub base_image: Option<String>,
+	///	The tarball compression format: `gzip`, `xz`, or `zstd`. Defaults
+	///	to `gzip`.
+	#[serde(default)]
+	pub compression: Compression,
+	///	Extra files (shell completions, man pages, systemd units, default
+	///	configs, ...) to bundle into the tarball and install from
+	///	`package()`.
+	#[serde(default)]
+	pub assets: Vec<Asset>,
+	///	The name of a `.install` hook script to ship alongside the
+	///	PKGBUILD, e.g. `"foo.install"`.
+	#[serde(default)]
+	pub install: Option<String>,
+	///	Where a `--source` PKGBUILD's `source` should point: the published
+	///	`.crate` on crates.io, or the repository's git tag. Defaults to
+	///	`crates-io`.
+	#[serde(default)]
+	pub source_origin: SourceOrigin,
 }
 
 impl Default for Aur {
@@ -102,10 +187,50 @@ impl Default for Aur {
 			name: None,
 			depends: Vec::new(),
 			optdepends: Vec::new(),
+			targets: Vec::new(),
+			base_image: None,
+			compression: Compression::default(),
+			assets: Vec::new(),
+			install: None,
+			source_origin: SourceOrigin::default(),
 		}
 	}
 }
 
+///	Where a from-source PKGBUILD resolves its `source` archive from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceOrigin {
+	CratesIo,
+	Git,
+}
+
+impl Default for SourceOrigin {
+	fn default() -> SourceOrigin {
+		SourceOrigin::CratesIo
+	}
+}
+
+///	A single extra file to install from `package()`, beyond the binary and
+///	licenses cargo-aur already handles.
+#[derive(Debug, Deserialize)]
+pub struct Asset {
+	///	The path to the file, relative to the project root.
+	pub source: String,
+	///	The path it should be installed to, relative to `$pkgdir`, e.g.
+	///	`"/usr/share/bash-completion/completions/foo"`.
+	pub dest: String,
+	///	The permission mode passed to `install -D`, e.g. `"644"`.
+	#[serde(default = "Asset::default_mode")]
+	pub mode: String,
+}
+
+impl Asset {
+	fn default_mode() -> String {
+		"644".to_string()
+	}
+}
+
 impl std::fmt::Display for Aur {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		let (deps, opts) = (self.depends.as_slice(), self.optdepends.as_slice());
@@ -142,23 +267,75 @@ pub enum GitHost {
 }
 
 impl GitHost {
-	///	The expected tarball location for a Package.
-	pub fn source(&self, package: &Package) -> String {
+	///	The expected tarball location for a Package, for the given Arch
+	///	architecture name and compression format.
+	pub fn source(&self, package: &Package, arch: &str, compression: Compression) -> String {
 		let path = std::env::var("CARGO_AUR_ARCHIVE").ok();
 		if let Some(path) = path { return path; }
 
 		let repository = &package.repository;
 		let name = &package.name;
+		let ext = compression.extension();
 
 		match self {
 			GitHost::Github => format!(
-				"{repository}/releases/download/v$pkgver/{name}-$pkgver-x86_64.tar.gz"
+				"{repository}/releases/download/v$pkgver/{name}-$pkgver-{arch}.{ext}"
 			),
 			GitHost::Gitlab => format!(
-				"{repository}/-/archive/v$pkgver/{name}-$pkgver-x86_64.tar.gz"
+				"{repository}/-/archive/v$pkgver/{name}-$pkgver-{arch}.{ext}"
 			),
 		}
 	}
+
+	///	The expected source archive location for a Package's git tag at
+	///	the given version, used by a `--source` PKGBUILD. Pass `"$pkgver"`
+	///	for the templated PKGBUILD field, or a concrete version to resolve
+	///	a downloadable URL.
+	pub fn tag_source_at(&self, package: &Package, version: &str) -> String {
+		let repository = &package.repository;
+		let name = &package.name;
+
+		match self {
+			GitHost::Github => format!("{repository}/archive/refs/tags/v{version}.tar.gz"),
+			GitHost::Gitlab => format!("{repository}/-/archive/v{version}/{name}-v{version}.tar.gz"),
+		}
+	}
+
+	///	The templated source archive location for a Package's git tag, for
+	///	a `--source` PKGBUILD's `source` field.
+	pub fn tag_source(&self, package: &Package) -> String {
+		self.tag_source_at(package, "$pkgver")
+	}
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn arch_name_maps_known_triples() {
+		assert_eq!(arch_name("x86_64-unknown-linux-gnu"), "x86_64");
+		assert_eq!(arch_name("aarch64-unknown-linux-gnu"), "aarch64");
+		assert_eq!(arch_name("armv7-unknown-linux-gnueabihf"), "armv7h");
+	}
+
+	#[test]
+	fn arch_name_passes_through_unrecognized_triples() {
+		assert_eq!(arch_name("riscv64gc-unknown-linux-gnu"), "riscv64gc-unknown-linux-gnu");
+	}
+
+	#[test]
+	fn compression_tar_args() {
+		assert_eq!(Compression::Gzip.tar_args(), &["czf"]);
+		assert_eq!(Compression::Xz.tar_args(), &["cJf"]);
+		assert_eq!(Compression::Zstd.tar_args(), &["--zstd", "-cf"]);
+	}
+
+	#[test]
+	fn compression_extension() {
+		assert_eq!(Compression::Gzip.extension(), "tar.gz");
+		assert_eq!(Compression::Xz.extension(), "tar.xz");
+		assert_eq!(Compression::Zstd.extension(), "tar.zst");
+	}
+}
 
@@ -0,0 +1,108 @@
+//!	Collection of third-party dependency license texts, bundled into the
+//!	release tarball as a single `THIRD-PARTY-LICENSES` file. This matters
+//!	for statically-linked (musl) binaries, which embed dependency code
+//!	directly instead of dynamically linking against it.
+
+use crate::error::Error;
+use cargo_metadata::MetadataCommand;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+///	A dependency's resolved license: its name, version, and either the text
+///	of a `LICENSE`/`COPYING` file from its source directory or a fallback
+///	SPDX note.
+pub struct DependencyLicense {
+	pub name: String,
+	pub version: String,
+	pub text: String,
+}
+
+///	Walk the full dependency tree via `cargo metadata` and resolve each
+///	third-party crate's license. The workspace's own member(s) — the crate
+///	actually being packaged — are excluded; they aren't a "third-party"
+///	dependency.
+pub fn collect() -> Result<Vec<DependencyLicense>, Error> {
+	let metadata = MetadataCommand::new()
+		.exec()
+		.map_err(|_| Error::CargoMetadata)?;
+
+	let workspace_members = &metadata.workspace_members;
+
+	let mut licenses: Vec<DependencyLicense> = metadata
+		.packages
+		.iter()
+		.filter(|package| !workspace_members.contains(&package.id))
+		.map(|package| {
+			let text = package
+				.manifest_path
+				.parent()
+				.and_then(|dir| license_file_text(dir.as_std_path()))
+				.unwrap_or_else(|| match &package.license {
+					Some(spdx) => format!(
+						"No license file was bundled with this crate. SPDX identifier: {spdx}\n"
+					),
+					None => "No license information is available for this crate.\n".to_string(),
+				});
+
+			DependencyLicense {
+				name: package.name.clone(),
+				version: package.version.to_string(),
+				text,
+			}
+		})
+		.collect();
+
+	licenses.sort_by(|a, b| a.name.cmp(&b.name));
+	Ok(licenses)
+}
+
+///	Find and read every `LICENSE*`/`COPYING*` file in a crate's source
+///	directory, mirroring `license_file` in `main.rs`. Dual (or triple)
+///	licensed crates commonly ship more than one of these (e.g.
+///	`LICENSE-MIT` and `LICENSE-APACHE`); all of them are concatenated, in a
+///	fixed name order, rather than picking whichever `fs::read_dir` happens
+///	to return first.
+fn license_file_text(dir: &Path) -> Option<String> {
+	let mut entries: Vec<_> = fs::read_dir(dir)
+		.ok()?
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| {
+			entry
+				.file_name()
+				.to_str()
+				.map(|s| s.starts_with("LICENSE") || s.starts_with("COPYING"))
+				.unwrap_or(false)
+		})
+		.collect();
+	entries.sort_by_key(|entry| entry.file_name());
+
+	let texts: Vec<String> = entries
+		.iter()
+		.filter_map(|entry| fs::read_to_string(entry.path()).ok())
+		.collect();
+
+	(!texts.is_empty()).then(|| texts.join("\n"))
+}
+
+///	Render the collected licenses into the contents of a
+///	`THIRD-PARTY-LICENSES` file, deduplicating crates that share identical
+///	license text.
+pub fn render(licenses: &[DependencyLicense]) -> String {
+	let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+	for license in licenses {
+		groups
+			.entry(license.text.as_str())
+			.or_default()
+			.push(format!("{} {}", license.name, license.version));
+	}
+
+	let mut out = String::new();
+	for (text, crates) in groups {
+		out.push_str(&crates.join(", "));
+		out.push_str("\n\n");
+		out.push_str(text);
+		out.push_str("\n--------------------------------------------------------------------------------\n\n");
+	}
+	out
+}
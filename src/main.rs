@@ -1,7 +1,8 @@
 mod config;
 mod error;
+mod licenses;
 
-use crate::config::{LICENSES, Config, Package};
+use crate::config::{LICENSES, Compression, Config, Package, SourceOrigin};
 use crate::error::Error;
 use colored::*;
 use gumdrop::{Options, ParsingStyle};
@@ -23,6 +24,11 @@ struct Args {
     musl: bool,
     /// Don't actually build anything.
     dryrun: bool,
+    /// Build the generated PKGBUILD in a clean container to confirm it installs.
+    verify: bool,
+    /// Generate a from-source PKGBUILD that builds on the user's machine,
+    /// instead of downloading a prebuilt release tarball.
+    source: bool,
     /// collect unused free arg(s) so "cargo aur" doesn't panic
     #[options(free)]
     _free: Vec<String>
@@ -59,9 +65,14 @@ fn main() -> ExitCode {
 
 /// Main program body, wrapped for error handling.
 fn work(args: Args) -> Result<(), Error> {
-    // We can't proceed if the user has specified `--musl` but doesn't have the
-    // target installed.
-    if args.musl {
+    // Read config from Cargo.toml
+    let config = cargo_config()?;
+
+    // We can't proceed if the user has specified `--musl` but doesn't have
+    // the target installed. Irrelevant to `--source` (never builds musl)
+    // and to multi-target builds, where `targets` selects triples
+    // explicitly and `--musl` has no effect.
+    if args.musl && !args.source && config.package.metadata.aur.targets.is_empty() {
         p("Checking for musl toolchain...".bold());
         musl_check()?
     }
@@ -80,9 +91,6 @@ fn work(args: Args) -> Result<(), Error> {
     // operation later on will fail.
     std::fs::create_dir_all(&output)?;
 
-    // Read config from Cargo.toml
-    let config = cargo_config()?;
-
     // Copy license file if needed
     let license = if must_copy_license(&config.package.license) {
         p("LICENSE file will be installed manually.".bold().yellow());
@@ -96,14 +104,58 @@ fn work(args: Args) -> Result<(), Error> {
         return Ok(());
     }
 
-    release_build(args.musl)?;
-    tarball(args.musl, &cargo_target, &output, license.as_ref(), &config)?;
-    let sha256: String = sha256sum(&config.package, &output)?;
+    // A from-source PKGBUILD doesn't download or pack a prebuilt binary, so
+    // it skips the build/tarball/checksum pipeline below entirely.
+    if args.source {
+        return work_source(&output, &config, license.as_ref(), args.verify);
+    }
+
+    // Collect third-party dependency licenses for bundling into the tarball.
+    p("Collecting third-party dependency licenses...".bold());
+    let third_party_licenses = licenses::collect()?;
+    let third_party_path = PathBuf::from("THIRD-PARTY-LICENSES");
+    std::fs::write(&third_party_path, licenses::render(&third_party_licenses))?;
+
+    // Each build is a (target triple, Arch arch name) pair. With no
+    // `targets` configured, we fall back to a single build for the host's
+    // default (or musl) target, tagged as `x86_64`, matching historical
+    // behaviour.
+    let builds: Vec<(Option<String>, String)> = if config.package.metadata.aur.targets.is_empty() {
+        let triple = args.musl.then(|| "x86_64-unknown-linux-musl".to_string());
+        vec![(triple, "x86_64".to_string())]
+    } else {
+        config.package.metadata.aur.targets.iter()
+            .map(|triple| (Some(triple.clone()), config::arch_name(triple).to_string()))
+            .collect()
+    };
+
+    let mut artifacts = Vec::with_capacity(builds.len());
+    for (triple, arch) in &builds {
+        release_build(triple.as_deref())?;
+        tarball(triple.as_deref(), arch, &cargo_target, &output, license.as_ref(), &third_party_path, &config)?;
+        let sha256 = sha256sum(&config.package, &output, arch, config.package.metadata.aur.compression)?;
+        artifacts.push((arch.clone(), sha256));
+    }
+
+    std::fs::remove_file(&third_party_path)?;
+
+    // The AUR reads the `.install` hook from next to the PKGBUILD, not from
+    // inside the release tarball.
+    copy_install_hook(&output, &config)?;
 
     // Write the PKGBUILD.
     let path = output.join("PKGBUILD");
     let file = BufWriter::new(File::create(&path)?);
-    pkgbuild(file, &config, &sha256, license.as_ref())?;
+    pkgbuild(file, &config, &artifacts, license.as_ref())?;
+
+    // Write the .SRCINFO file the AUR's git submission workflow requires.
+    let srcinfo_path = output.join(".SRCINFO");
+    let srcinfo_file = BufWriter::new(File::create(&srcinfo_path)?);
+    srcinfo(srcinfo_file, &config, &artifacts)?;
+
+    if args.verify {
+        verify_build(&output, &config)?;
+    }
 
     Ok(())
 }
@@ -140,11 +192,21 @@ fn license_file() -> Result<DirEntry, Error> {
         .ok_or(Error::MissingLicense)
 }
 
+/// Copy a configured `.install` hook into `output`, next to the PKGBUILD and
+/// `.SRCINFO`. `makepkg` and the AUR read the `install=` file from there, not
+/// from `$srcdir`, so it must not be bundled into the release tarball.
+fn copy_install_hook(output: &Path, config: &Config) -> Result<(), Error> {
+    if let Some(install_hook) = &config.package.metadata.aur.install {
+        std::fs::copy(install_hook, output.join(install_hook))?;
+    }
+    Ok(())
+}
+
 /// Write a legal PKGBUILD to some `Write` instance (a `File` in this case).
 fn pkgbuild<T>(
     mut file: T,
     config: &Config,
-    sha256: &str,
+    artifacts: &[(String, String)],
     license: Option<&DirEntry>,
 ) -> Result<(), Error>
 where
@@ -162,8 +224,6 @@ where
     let metadata = &package.metadata.aur;
     let package_name = metadata.name.clone()
         .unwrap_or( format!("{}-bin", package.name) );
-    let source = metadata.archive.clone()
-        .unwrap_or( package.git_host().source(&config.package) );
     let dependencies = format!("{}", metadata);
 
     // Write PKGBUILD
@@ -180,26 +240,295 @@ where
     writeln!(file, "pkgdesc=\"{}\"", package.description)?;
     writeln!(file, "url=\"{}\"", package.homepage)?;
     writeln!(file, "license=(\"{}\")", package.license)?;
-    writeln!(file, "arch=(\"x86_64\")")?;
+
+    let arches = artifacts.iter()
+        .map(|(arch, _)| format!("\"{}\"", arch))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(file, "arch=({})", arches)?;
     writeln!(file, "provides=(\"{}\")", package.name)?;
     writeln!(file, "conflicts=(\"{}\")", package.name)?;
 
+    if let Some(install_hook) = &metadata.install {
+        writeln!(file, "install={}", install_hook)?;
+    }
+
     if dependencies.len() > 0 {
         writeln!(file, "{}", metadata)?;
     }
 
-    // If source property is not a URL, make it relative to the repository
+    // A single architecture keeps the classic, non-suffixed `source`/
+    // `sha256sums` fields. Multiple architectures require the
+    // `source_<arch>`/`sha256sums_<arch>` PKGBUILD convention instead.
+    if let [(arch, sha256)] = artifacts {
+        writeln!(file, "source=(\"{}\")", resolved_source(config, arch))?;
+        writeln!(file, "sha256sums=(\"{}\")", sha256)?;
+    } else {
+        for (arch, sha256) in artifacts {
+            writeln!(file, "source_{}=(\"{}\")", arch, resolved_source(config, arch))?;
+            writeln!(file, "sha256sums_{}=(\"{}\")", arch, sha256)?;
+        }
+    }
+    writeln!(file)?;
+    writeln!(file, "package() {{")?;
+    writeln!(
+        file,
+        "    install -Dm755 {} -t \"$pkgdir/usr/bin\"",
+        config.binary_name()
+    )?;
+
+    if let Some(lic) = license {
+        let file_name = lic
+            .file_name()
+            .into_string()
+            .map_err(|_| Error::Utf8OsString)?;
+        writeln!(
+            file,
+            "    install -Dm644 {} \"$pkgdir/usr/share/licenses/$pkgname/{}\"",
+            file_name, file_name
+        )?;
+    }
+
+    writeln!(
+        file,
+        "    install -Dm644 THIRD-PARTY-LICENSES \"$pkgdir/usr/share/licenses/$pkgname/THIRD-PARTY\""
+    )?;
+
+    for asset in &metadata.assets {
+        writeln!(
+            file,
+            "    install -Dm{} {} \"$pkgdir{}\"",
+            asset.mode, asset.source, asset.dest
+        )?;
+    }
+
+    writeln!(file, "}}")?;
+    Ok(())
+}
+
+/// Resolve the final `source` URL for a given architecture, falling back to
+/// the package's git host when no `archive` override is configured. Any
+/// `{arch}` placeholder in the override is substituted with `arch`, so a
+/// multi-`targets` PKGBUILD doesn't end up with every architecture pointing
+/// at the same archive.
+fn resolved_source(config: &Config, arch: &str) -> String {
+    let package = &config.package;
+    let metadata = &package.metadata.aur;
+    let source = metadata.archive.clone()
+        .map(|archive| archive.replace("{arch}", arch))
+        .unwrap_or_else(|| package.git_host().source(package, arch, metadata.compression));
+
     if !source.starts_with("https://") {
-        writeln!(file, "source=(\"{}/{}\")", package.repository, source)?;
+        format!("{}/{}", package.repository, source)
+    } else {
+        source
+    }
+}
+
+/// The Arch architectures a from-source PKGBUILD should declare, derived
+/// from `[package.metadata.aur] targets` the same way the prebuilt-binary
+/// path derives its `builds` list. A from-source build isn't tied to any
+/// particular triple (`cargo build --release` targets the host), so this is
+/// purely advertisory; it defaults to `x86_64` when `targets` is empty.
+fn source_arches(config: &Config) -> Vec<&str> {
+    let targets = &config.package.metadata.aur.targets;
+    if targets.is_empty() {
+        vec!["x86_64"]
+    } else {
+        targets.iter().map(|triple| config::arch_name(triple)).collect()
+    }
+}
+
+/// Write a `.SRCINFO` file alongside the PKGBUILD, generated directly from
+/// the same `Config`/checksum data used by `pkgbuild()`. The AUR's git
+/// submission workflow requires this file, and generating it ourselves
+/// (rather than shelling out to `makepkg --printsrcinfo`) means the tool
+/// also works on non-Arch CI runners.
+fn srcinfo<T>(mut file: T, config: &Config, artifacts: &[(String, String)]) -> Result<(), Error>
+where
+    T: Write,
+{
+    let package = &config.package;
+    let metadata = &package.metadata.aur;
+    let package_name = metadata.name.clone()
+        .unwrap_or( format!("{}-bin", package.name) );
+
+    writeln!(file, "pkgbase = {}", package_name)?;
+    writeln!(file, "\tpkgdesc = {}", package.description)?;
+    writeln!(file, "\tpkgver = {}", package.version)?;
+    writeln!(file, "\tpkgrel = 1")?;
+    writeln!(file, "\turl = {}", package.homepage)?;
+
+    for (arch, _) in artifacts {
+        writeln!(file, "\tarch = {}", arch)?;
+    }
+
+    writeln!(file, "\tlicense = {}", package.license)?;
+    writeln!(file, "\tprovides = {}", package.name)?;
+    writeln!(file, "\tconflicts = {}", package.name)?;
+
+    if let Some(install_hook) = &metadata.install {
+        writeln!(file, "\tinstall = {}", install_hook)?;
+    }
+
+    for dep in &metadata.depends {
+        writeln!(file, "\tdepends = {}", dep)?;
+    }
+    for opt in &metadata.optdepends {
+        writeln!(file, "\toptdepends = {}", opt)?;
+    }
+
+    if let [(arch, sha256)] = artifacts {
+        writeln!(file, "\tsource = {}", resolved_source(config, arch))?;
+        writeln!(file, "\tsha256sums = {}", sha256)?;
     } else {
-        writeln!(file, "source=(\"{}\")", source)?;
+        for (arch, sha256) in artifacts {
+            writeln!(file, "\tsource_{} = {}", arch, resolved_source(config, arch))?;
+            writeln!(file, "\tsha256sums_{} = {}", arch, sha256)?;
+        }
+    }
+
+    writeln!(file)?;
+    writeln!(file, "pkgname = {}", package_name)?;
+
+    Ok(())
+}
+
+/// Generate a from-source PKGBUILD (and its `.SRCINFO`) that builds on the
+/// user's machine, instead of downloading a prebuilt release tarball.
+fn work_source(
+    output: &Path,
+    config: &Config,
+    license: Option<&DirEntry>,
+    verify: bool,
+) -> Result<(), Error> {
+    let package = &config.package;
+
+    p("Resolving source archive...".bold());
+    let (source, concrete) = match config.package.metadata.aur.source_origin {
+        SourceOrigin::CratesIo => {
+            let url = format!(
+                "https://static.crates.io/crates/{}/{}-{}.crate",
+                package.name, package.name, package.version
+            );
+            // crates.io serves `.crate` files, an extension `makepkg`/`bsdtar`
+            // won't auto-extract, so rename it to a `.tar.gz` on download.
+            let renamed = format!("$pkgname-$pkgver.tar.gz::{}", url);
+            (renamed, url)
+        }
+        SourceOrigin::Git => (
+            package.git_host().tag_source(package),
+            package.git_host().tag_source_at(package, &package.version),
+        ),
+    };
+    let sha256 = download_sha256(&concrete)?;
+
+    copy_install_hook(output, config)?;
+
+    let path = output.join("PKGBUILD");
+    let file = BufWriter::new(File::create(&path)?);
+    source_pkgbuild(file, config, &source, &sha256, license)?;
+
+    let srcinfo_path = output.join(".SRCINFO");
+    let srcinfo_file = BufWriter::new(File::create(&srcinfo_path)?);
+    source_srcinfo(srcinfo_file, config, &source, &sha256)?;
+
+    if verify {
+        verify_build(output, config)?;
+    }
+
+    Ok(())
+}
+
+/// Download a URL and hash its contents, the same way `sha256sum()` hashes
+/// a local tarball.
+fn download_sha256(url: &str) -> Result<String, Error> {
+    p(format!("Downloading {} to compute its checksum...", url).bold());
+    let output = Command::new("curl").args(["-sLf", url]).output()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(Error::DownloadFailed(url.to_string()));
     }
+    let digest = Hash::hash(&output.stdout);
+    Ok(digest.iter().map(|u| format!("{:02x}", u)).collect())
+}
+
+/// Write a from-source PKGBUILD: `source` points at the crate's published
+/// `.crate` or git tag, `makedepends` pulls in the Rust toolchain, and
+/// `build()`/`package()` compile and install from `target/release` instead
+/// of downloading a prebuilt binary.
+fn source_pkgbuild<T>(
+    mut file: T,
+    config: &Config,
+    source: &str,
+    sha256: &str,
+    license: Option<&DirEntry>,
+) -> Result<(), Error>
+where
+    T: Write,
+{
+    let package = &config.package;
+    let authors = package
+        .authors
+        .iter()
+        .map(|a| format!("# Maintainer: {}", a))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let metadata = &package.metadata.aur;
+    let package_name = metadata.name.clone().unwrap_or(package.name.clone());
+    let dependencies = format!("{}", metadata);
+
+    writeln!(file, "{}", authors)?;
+    writeln!(file, "#")?;
+    writeln!(
+        file,
+        "# This PKGBUILD was generated by `cargo aur`: https://crates.io/crates/cargo-aur"
+    )?;
+    writeln!(file)?;
+    writeln!(file, "pkgname={}", package_name)?;
+    writeln!(file, "pkgver={}", package.version)?;
+    writeln!(file, "pkgrel=1")?;
+    writeln!(file, "pkgdesc=\"{}\"", package.description)?;
+    writeln!(file, "url=\"{}\"", package.homepage)?;
+    writeln!(file, "license=(\"{}\")", package.license)?;
+
+    let arches = source_arches(config).iter()
+        .map(|arch| format!("\"{}\"", arch))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(file, "arch=({})", arches)?;
+    writeln!(file, "makedepends=(\"cargo\" \"rust\")")?;
+    writeln!(file, "provides=(\"{}\")", package.name)?;
+    writeln!(file, "conflicts=(\"{}\")", package.name)?;
+
+    if let Some(install_hook) = &metadata.install {
+        writeln!(file, "install={}", install_hook)?;
+    }
+
+    if dependencies.len() > 0 {
+        writeln!(file, "{}", metadata)?;
+    }
+
+    writeln!(file, "source=(\"{}\")", source)?;
     writeln!(file, "sha256sums=(\"{}\")", sha256)?;
     writeln!(file)?;
+
+    // The extracted source directory is always named after the crate's
+    // actual `Cargo.toml` name (what `cargo package`/the git host's
+    // tag-archive both produce), never the AUR `pkgname`, which may have
+    // been overridden by `[package.metadata.aur] name`.
+    writeln!(file, "build() {{")?;
+    writeln!(file, "    cd \"{}-$pkgver\"", package.name)?;
+    writeln!(file, "    export CARGO_TARGET_DIR=\"${{CARGO_TARGET_DIR:-target}}\"")?;
+    writeln!(file, "    cargo build --release --frozen")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
     writeln!(file, "package() {{")?;
+    writeln!(file, "    cd \"{}-$pkgver\"", package.name)?;
     writeln!(
         file,
-        "    install -Dm755 {} -t \"$pkgdir/usr/bin\"",
+        "    install -Dm755 \"${{CARGO_TARGET_DIR:-target}}/release/{}\" -t \"$pkgdir/usr/bin\"",
         config.binary_name()
     )?;
 
@@ -215,16 +544,125 @@ where
         )?;
     }
 
+    for asset in &metadata.assets {
+        writeln!(
+            file,
+            "    install -Dm{} {} \"$pkgdir{}\"",
+            asset.mode, asset.source, asset.dest
+        )?;
+    }
+
     writeln!(file, "}}")?;
     Ok(())
 }
 
-/// Run `cargo build --release`, potentially building statically.
-fn release_build(musl: bool) -> Result<(), Error> {
-    let mut args = vec!["build", "--release"];
+/// Write the `.SRCINFO` for a from-source PKGBUILD, mirroring `srcinfo()`.
+fn source_srcinfo<T>(mut file: T, config: &Config, source: &str, sha256: &str) -> Result<(), Error>
+where
+    T: Write,
+{
+    let package = &config.package;
+    let metadata = &package.metadata.aur;
+    let package_name = metadata.name.clone().unwrap_or(package.name.clone());
+
+    writeln!(file, "pkgbase = {}", package_name)?;
+    writeln!(file, "\tpkgdesc = {}", package.description)?;
+    writeln!(file, "\tpkgver = {}", package.version)?;
+    writeln!(file, "\tpkgrel = 1")?;
+    writeln!(file, "\turl = {}", package.homepage)?;
+
+    for arch in source_arches(config) {
+        writeln!(file, "\tarch = {}", arch)?;
+    }
+
+    writeln!(file, "\tlicense = {}", package.license)?;
+    writeln!(file, "\tmakedepends = cargo")?;
+    writeln!(file, "\tmakedepends = rust")?;
+    writeln!(file, "\tprovides = {}", package.name)?;
+    writeln!(file, "\tconflicts = {}", package.name)?;
+
+    if let Some(install_hook) = &metadata.install {
+        writeln!(file, "\tinstall = {}", install_hook)?;
+    }
+
+    for dep in &metadata.depends {
+        writeln!(file, "\tdepends = {}", dep)?;
+    }
+    for opt in &metadata.optdepends {
+        writeln!(file, "\toptdepends = {}", opt)?;
+    }
+
+    writeln!(file, "\tsource = {}", source)?;
+    writeln!(file, "\tsha256sums = {}", sha256)?;
+    writeln!(file)?;
+    writeln!(file, "pkgname = {}", package_name)?;
+
+    Ok(())
+}
+
+/// Build the generated PKGBUILD inside a clean, unprivileged Arch Linux
+/// container to confirm it actually installs, following Malachite's
+/// containerized `makepkg` approach. Catches malformed `depends`, bad
+/// `source`/`sha256sums`, and missing license-install lines before the
+/// user pushes to the AUR.
+fn verify_build(output: &Path, config: &Config) -> Result<(), Error> {
+    let base_image = config.package.metadata.aur.base_image.as_deref().unwrap_or("archlinux");
+
+    p("Writing verification Dockerfile...".bold());
+    let dockerfile = format!(
+        "FROM {base_image}\n\
+         RUN pacman -Syu --noconfirm --needed base-devel sudo\n\
+         RUN useradd -m build-user\n\
+         RUN echo 'build-user ALL=(ALL) NOPASSWD: ALL' > /etc/sudoers.d/build-user\n\
+         COPY --chown=build-user:build-user . /home/build-user/pkg\n\
+         WORKDIR /home/build-user/pkg\n\
+         USER build-user\n\
+         RUN makepkg -s --noconfirm\n"
+    );
+    std::fs::write(output.join("Dockerfile"), dockerfile)?;
+
+    let image = format!("cargo-aur-verify-{}", config.package.name);
+    p("Building verification image...".bold());
+    let build_status = Command::new("docker")
+        .args(["build", "-t", &image])
+        .arg(output)
+        .status()?;
+    if !build_status.success() {
+        return Err(Error::VerifyFailed);
+    }
+
+    p("Copying the built package out of the container...".bold());
+    let container = format!("cargo-aur-verify-{}", config.package.name);
+    Command::new("docker").args(["rm", "-f", &container]).status().ok();
+    let create_status = Command::new("docker")
+        .args(["create", "--name", &container])
+        .arg(&image)
+        .status()?;
+    if !create_status.success() {
+        return Err(Error::VerifyFailed);
+    }
+
+    let copy_status = Command::new("docker")
+        .args(["cp", &format!("{container}:/home/build-user/pkg/.")])
+        .arg(output)
+        .status()?;
+    Command::new("docker").args(["rm", "-f", &container]).status().ok();
+
+    if !copy_status.success() {
+        return Err(Error::VerifyFailed);
+    }
+
+    p("Verification build succeeded.".bold().green());
+    Ok(())
+}
+
+/// Run `cargo build --release`, optionally cross-building for a specific
+/// target triple.
+fn release_build(target: Option<&str>) -> Result<(), Error> {
+    let mut args = vec!["build".to_string(), "--release".to_string()];
 
-    if musl {
-        args.push("--target=x86_64-unknown-linux-musl");
+    if let Some(triple) = target {
+        args.push(format!("--target={}", triple));
     }
 
     p("Running release build...".bold());
@@ -233,16 +671,17 @@ fn release_build(musl: bool) -> Result<(), Error> {
 }
 
 fn tarball(
-    musl: bool,
+    target: Option<&str>,
+    arch: &str,
     cargo_target: &Path,
     output: &Path,
     license: Option<&DirEntry>,
+    third_party_licenses: &Path,
     config: &Config,
 ) -> Result<(), Error> {
-    let release_dir = if musl {
-        "x86_64-unknown-linux-musl/release"
-    } else {
-        "release"
+    let release_dir = match target {
+        Some(triple) => PathBuf::from(triple).join("release"),
+        None => PathBuf::from("release"),
     };
 
     let binary_name = config.binary_name();
@@ -252,15 +691,22 @@ fn tarball(
     std::fs::copy(binary, binary_name)?;
 
     // Create the tarball.
-    p("Packing tarball...".bold());
+    let compression = config.package.metadata.aur.compression;
+    p(format!("Packing {} tarball...", arch).bold());
     let mut command = Command::new("tar");
     command
-        .arg("czf")
-        .arg(config.package.tarball(output))
-        .arg(binary_name);
+        .args(compression.tar_args())
+        .arg(config.package.tarball(output, arch, compression))
+        .arg(binary_name)
+        .arg(third_party_licenses);
     if let Some(lic) = license {
         command.arg(lic.path());
     }
+
+    for asset in &config.package.metadata.aur.assets {
+        command.arg(&asset.source);
+    }
+
     command.status()?;
 
     std::fs::remove_file(binary_name)?;
@@ -276,8 +722,8 @@ fn strip(path: &Path) -> Result<(), Error> {
     Ok(()) // FIXME Would love to use my `void` package here and elsewhere.
 }
 
-fn sha256sum(package: &Package, output: &Path) -> Result<String, Error> {
-    let bytes = std::fs::read(package.tarball(output))?;
+fn sha256sum(package: &Package, output: &Path, arch: &str, compression: Compression) -> Result<String, Error> {
+    let bytes = std::fs::read(package.tarball(output, arch, compression))?;
     let digest = Hash::hash(&bytes);
     let hex = digest.iter().map(|u| format!("{:02x}", u)).collect();
     Ok(hex)
@@ -298,3 +744,123 @@ fn musl_check() -> Result<(), Error> {
 fn p(msg: ColoredString) {
     println!("{} {}", "::".bold(), msg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a minimal `Cargo.toml` body (plus whatever `[package.metadata.aur]`
+    /// block a test needs) the same way `cargo_config()` parses the real thing.
+    fn test_config(aur_block: &str) -> Config {
+        let toml = format!(
+            r#"
+            [package]
+            name = "demo"
+            version = "1.2.3"
+            authors = ["Someone <someone@example.com>"]
+            description = "A demo package"
+            homepage = "https://example.com"
+            repository = "https://github.com/someone/demo"
+            license = "MIT"
+
+            {aur_block}
+            "#
+        );
+        toml::from_str(&toml).expect("valid test Cargo.toml")
+    }
+
+    #[test]
+    fn source_arches_defaults_to_x86_64() {
+        let config = test_config("");
+        assert_eq!(source_arches(&config), vec!["x86_64"]);
+    }
+
+    #[test]
+    fn source_arches_derives_from_targets() {
+        let config = test_config(
+            r#"
+            [package.metadata.aur]
+            targets = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"]
+            "#,
+        );
+        assert_eq!(source_arches(&config), vec!["x86_64", "aarch64"]);
+    }
+
+    #[test]
+    fn resolved_source_falls_back_to_git_host() {
+        let config = test_config("");
+        assert_eq!(
+            resolved_source(&config, "x86_64"),
+            "https://github.com/someone/demo/releases/download/v$pkgver/demo-$pkgver-x86_64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn resolved_source_substitutes_arch_placeholder_in_archive_override() {
+        let config = test_config(
+            r#"
+            [package.metadata.aur]
+            archive = "releases/demo-{arch}.tar.gz"
+            "#,
+        );
+        assert_eq!(
+            resolved_source(&config, "aarch64"),
+            "https://github.com/someone/demo/releases/demo-aarch64.tar.gz"
+        );
+        assert_eq!(
+            resolved_source(&config, "x86_64"),
+            "https://github.com/someone/demo/releases/demo-x86_64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn pkgbuild_and_srcinfo_agree_on_provides_and_conflicts() {
+        let config = test_config("");
+        let artifacts = vec![("x86_64".to_string(), "deadbeef".to_string())];
+
+        let mut built = Vec::new();
+        pkgbuild(&mut built, &config, &artifacts, None).unwrap();
+        let built = String::from_utf8(built).unwrap();
+        assert!(built.contains("provides=(\"demo\")"));
+        assert!(built.contains("conflicts=(\"demo\")"));
+
+        let mut info = Vec::new();
+        srcinfo(&mut info, &config, &artifacts).unwrap();
+        let info = String::from_utf8(info).unwrap();
+        assert!(info.contains("\tprovides = demo"));
+        assert!(info.contains("\tconflicts = demo"));
+    }
+
+    #[test]
+    fn source_pkgbuild_cds_into_the_crate_name_directory_not_pkgname() {
+        let config = test_config(
+            r#"
+            [package.metadata.aur]
+            name = "demo-bin-renamed"
+            "#,
+        );
+
+        let mut built = Vec::new();
+        source_pkgbuild(&mut built, &config, "https://example.com/demo.crate", "deadbeef", None).unwrap();
+        let built = String::from_utf8(built).unwrap();
+        assert!(built.contains("cd \"demo-$pkgver\""));
+        assert!(!built.contains("cd \"$pkgname-$pkgver\""));
+    }
+
+    #[test]
+    fn source_pkgbuild_and_source_srcinfo_agree_on_provides_and_conflicts() {
+        let config = test_config("");
+
+        let mut built = Vec::new();
+        source_pkgbuild(&mut built, &config, "https://example.com/demo.crate", "deadbeef", None).unwrap();
+        let built = String::from_utf8(built).unwrap();
+        assert!(built.contains("provides=(\"demo\")"));
+        assert!(built.contains("conflicts=(\"demo\")"));
+
+        let mut info = Vec::new();
+        source_srcinfo(&mut info, &config, "https://example.com/demo.crate", "deadbeef").unwrap();
+        let info = String::from_utf8(info).unwrap();
+        assert!(info.contains("\tprovides = demo"));
+        assert!(info.contains("\tconflicts = demo"));
+    }
+}